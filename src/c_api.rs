@@ -1,6 +1,8 @@
 use std::{
     os::raw::{c_double, c_void},
+    panic::{catch_unwind, UnwindSafe},
     ptr::slice_from_raw_parts,
+    sync::Mutex,
 };
 
 use cres::{
@@ -15,7 +17,7 @@ use cres::{
     ParticleID,
 };
 
-use crate::resampler::{Resampler, ResamplerBuilder};
+use crate::resampler::{CellStats, Resampler, ResamplerBuilder};
 
 /// Resampling options
 #[repr(C)]
@@ -27,24 +29,142 @@ pub struct Opt {
     ///
     /// This parameter corresponds to the τ parameter of
     /// [arXiv:2109.07851](https://arxiv.org/abs/2109.07851)
+    ///
+    /// Ignored if `dist_fn` is set.
     pt_weight: c_double,
+    /// Optional user-supplied distance function
+    ///
+    /// If non-null, overrides the built-in `EuclWithScaledPt` metric.
+    /// Called with a pair of events and `userdata` as its last argument
+    /// whenever the neighbour search needs the distance between them.
+    dist_fn: Option<
+        extern "C" fn(
+            a: *const EventView,
+            b: *const EventView,
+            userdata: *mut c_void,
+        ) -> c_double,
+    >,
+    /// Opaque pointer forwarded unchanged to `dist_fn`
+    userdata: *mut c_void,
+}
+
+/// Status returned by every `extern "C"` function in this module
+///
+/// `ScresStatus::Ok` is the only variant indicating success. A panic
+/// unwinding across an FFI boundary is undefined behaviour, so every
+/// entry point below catches panics internally and reports them as
+/// `Panicked` instead of letting them propagate.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScresStatus {
+    /// The call completed successfully
+    Ok,
+    /// A pointer argument that must not be null was null
+    NullPointer,
+    /// An event index (`pos` or `seed`) was out of bounds
+    IndexOutOfBounds,
+    /// The resampler holds no events
+    EmptyResampler,
+    /// The call panicked internally; results are undefined
+    Panicked,
+}
+
+/// Diagnostics recorded while resampling a single cell
+///
+/// Optionally filled in by [`scres_resample`]. Lets callers tune
+/// `max_cell_size` and verify that the total cross section is
+/// preserved cell by cell.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct CellInfo {
+    /// Number of events that were part of the cell
+    pub n_events: usize,
+    /// Distance from the seed event to the cell boundary
+    pub radius: c_double,
+    /// Sum of the cell's event weights before resampling
+    pub weight_sum_before: c_double,
+    /// Sum of the cell's event weights after resampling
+    pub weight_sum_after: c_double,
+    /// Number of events whose total weight changed sign
+    pub n_sign_flips: usize,
+}
+
+impl From<CellStats> for CellInfo {
+    fn from(stats: CellStats) -> Self {
+        let CellStats {
+            n_events,
+            radius,
+            weight_sum_before,
+            weight_sum_after,
+            n_sign_flips,
+        } = stats;
+        Self {
+            n_events,
+            radius: radius.raw(),
+            weight_sum_before: weight_sum_before.raw(),
+            weight_sum_after: weight_sum_after.raw(),
+            n_sign_flips,
+        }
+    }
+}
+
+/// Run `f`, converting a panic into [`ScresStatus::Panicked`]
+///
+/// Every `extern "C"` function in this module routes its body through
+/// this helper so that no panic can unwind across the FFI boundary.
+fn guard(f: impl FnOnce() -> ScresStatus + UnwindSafe) -> ScresStatus {
+    catch_unwind(f).unwrap_or(ScresStatus::Panicked)
 }
 
 /// Create a new resampler
+///
+/// On success, the new resampler is written to `*out`.
+///
+/// # Safety
+/// - `out` must not be null.
+/// - If `opt.dist_fn` is set, it must be callable concurrently from
+///   multiple threads, since `scres_resample_many` invokes it from a
+///   thread pool, and `opt.userdata` must remain valid for as long as
+///   the resampler is alive.
 #[no_mangle]
 #[must_use]
-pub extern "C" fn scres_new(opt: Opt) -> *mut c_void {
-    let Opt {
-        neighbour_search,
-        pt_weight,
-    } = opt;
-    let dist = EuclWithScaledPt::new(n64(pt_weight));
-    let resampler = ResamplerBuilder::default()
-        .distance(dist)
-        .neighbour_search(neighbour_search)
-        .build();
-    let resampler: &mut dyn CResampler = Box::leak(Box::new(resampler));
-    Box::into_raw(Box::new(resampler)) as _
+pub unsafe extern "C" fn scres_new(
+    opt: Opt,
+    out: *mut *mut c_void,
+) -> ScresStatus {
+    if out.is_null() {
+        return ScresStatus::NullPointer;
+    }
+    guard(|| {
+        let Opt {
+            neighbour_search,
+            pt_weight,
+            dist_fn,
+            userdata,
+        } = opt;
+        let resampler: &mut dyn CResampler = match dist_fn {
+            Some(dist_fn) => {
+                let dist = CallbackDistance::new(dist_fn, userdata);
+                Box::leak(Box::new(FfiResampler::new(
+                    ResamplerBuilder::default()
+                        .distance(dist)
+                        .neighbour_search(neighbour_search)
+                        .build(),
+                )))
+            }
+            None => {
+                let dist = EuclWithScaledPt::new(n64(pt_weight));
+                Box::leak(Box::new(FfiResampler::new(
+                    ResamplerBuilder::default()
+                        .distance(dist)
+                        .neighbour_search(neighbour_search)
+                        .build(),
+                )))
+            }
+        };
+        *out = Box::into_raw(Box::new(resampler)) as _;
+        ScresStatus::Ok
+    })
 }
 
 /// Delete a resampler
@@ -53,9 +173,20 @@ pub extern "C" fn scres_new(opt: Opt) -> *mut c_void {
 /// The resampler must have been previous constructed with `scres_new`.
 ///
 #[no_mangle]
-pub unsafe extern "C" fn scres_free(scres: *mut c_void) {
-    assert!(!scres.is_null());
-    let _ = Box::from_raw(scres as *mut &mut dyn CResampler);
+#[must_use]
+pub unsafe extern "C" fn scres_free(scres: *mut c_void) -> ScresStatus {
+    if scres.is_null() {
+        return ScresStatus::NullPointer;
+    }
+    guard(|| {
+        // `scres_new` stores an outer `Box<&mut dyn CResampler>` pointing
+        // at a reference into the `Box::leak`ed resampler. Dropping the
+        // outer box alone only drops the reference, not the resampler
+        // it points to, so reconstruct and drop the leaked inner box too.
+        let r = Box::from_raw(scres as *mut &mut dyn CResampler);
+        drop(Box::from_raw(*r as *mut dyn CResampler));
+        ScresStatus::Ok
+    })
 }
 
 /// Reserve space for events (optional)
@@ -64,9 +195,19 @@ pub unsafe extern "C" fn scres_free(scres: *mut c_void) {
 /// The resampler must have been previous constructed with `scres_new`.
 ///
 #[no_mangle]
-pub unsafe extern "C" fn scres_reserve(scres: *mut c_void, cap: usize) {
-    let scres = scres as *mut &mut dyn CResampler;
-    (*scres).reserve(cap);
+#[must_use]
+pub unsafe extern "C" fn scres_reserve(
+    scres: *mut c_void,
+    cap: usize,
+) -> ScresStatus {
+    if scres.is_null() {
+        return ScresStatus::NullPointer;
+    }
+    guard(|| {
+        let scres = scres as *mut &mut dyn CResampler;
+        (*scres).reserve(cap);
+        ScresStatus::Ok
+    })
 }
 
 /// Add an event
@@ -75,31 +216,109 @@ pub unsafe extern "C" fn scres_reserve(scres: *mut c_void, cap: usize) {
 /// The resampler must have been previous constructed with `scres_new`.
 ///
 #[no_mangle]
+#[must_use]
 pub unsafe extern "C" fn scres_push_event(
     scres: *mut c_void,
     event: EventView,
-) {
-    let scres = scres as *mut &mut dyn CResampler;
-    (*scres).push(event);
+) -> ScresStatus {
+    if scres.is_null() {
+        return ScresStatus::NullPointer;
+    }
+    guard(|| {
+        let scres = scres as *mut &mut dyn CResampler;
+        (*scres).push(event);
+        ScresStatus::Ok
+    })
 }
 
 /// Construct a cell with the `n`th event as seed and resample
 ///
+/// If `info` is non-null, diagnostics about the constructed cell are
+/// written to `*info`.
+///
 /// # Safety
 /// The resampler must have been previous constructed with `scres_new`.
 ///
 #[no_mangle]
+#[must_use]
 pub unsafe extern "C" fn scres_resample(
     scres: *const c_void,
     seed: usize,
     max_cell_size: c_double,
-) {
-    let scres = scres as *const &mut dyn CResampler;
-    (*scres).resample_cell(seed, max_cell_size);
+    info: *mut CellInfo,
+) -> ScresStatus {
+    if scres.is_null() {
+        return ScresStatus::NullPointer;
+    }
+    guard(|| {
+        let scres = scres as *const &mut dyn CResampler;
+        if (*scres).len() == 0 {
+            return ScresStatus::EmptyResampler;
+        }
+        if seed >= (*scres).len() {
+            return ScresStatus::IndexOutOfBounds;
+        }
+        let stats = (*scres).resample_cell(seed, max_cell_size);
+        if !info.is_null() {
+            *info = stats;
+        }
+        ScresStatus::Ok
+    })
+}
+
+/// Construct and resample many cells in parallel, one per seed
+///
+/// If `seeds` is null, `n_seeds` is ignored and cells are seeded
+/// automatically at every event with negative total weight.
+///
+/// # Safety
+/// - The resampler must have been previous constructed with `scres_new`.
+/// - If non-null, `seeds` must point to an array of at least `n_seeds`
+///   elements.
+/// - Seed cells must be disjoint. Overlapping cells are still
+///   memory-safe, since individual weight reads and writes cannot
+///   race, but the results are not logically correct: two threads can
+///   race to resample a shared event, and whichever write lands last
+///   silently clobbers the other. This is not checked here.
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn scres_resample_many(
+    scres: *const c_void,
+    seeds: *const usize,
+    n_seeds: usize,
+    max_cell_size: c_double,
+) -> ScresStatus {
+    if scres.is_null() {
+        return ScresStatus::NullPointer;
+    }
+    guard(|| {
+        let scres = scres as *const &mut dyn CResampler;
+        if (*scres).len() == 0 {
+            return ScresStatus::EmptyResampler;
+        }
+        if seeds.is_null() {
+            (*scres).resample_all(max_cell_size);
+            return ScresStatus::Ok;
+        }
+        let seeds = std::slice::from_raw_parts(seeds, n_seeds);
+        if seeds.iter().any(|&seed| seed >= (*scres).len()) {
+            return ScresStatus::IndexOutOfBounds;
+        }
+        (*scres).resample_cells(seeds, max_cell_size);
+        ScresStatus::Ok
+    })
 }
 
 /// Returns the weights of the chosen event
 ///
+/// On success, the weight pointer is written to `*out`. It is backed by
+/// a scratch buffer owned by the resampler and is only valid until the
+/// *next call to `scres_get_weights` on the same handle*, whether or
+/// not that call reads the same event. It is not merely invalidated by
+/// calls that mutate the resampler. Callers that need several weight
+/// pointers to stay valid at once, or that read concurrently from
+/// multiple threads, should use `scres_copy_weights` instead.
+///
 /// # Safety
 /// The resampler must have been previous constructed with `scres_new`.
 ///
@@ -108,23 +327,81 @@ pub unsafe extern "C" fn scres_resample(
 pub unsafe extern "C" fn scres_get_weights(
     scres: *mut c_void,
     pos: usize,
-) -> *const c_double {
-    let scres = scres as *mut &mut dyn CResampler;
-    (*scres).get_weights(pos).as_ptr()
+    out: *mut *const c_double,
+) -> ScresStatus {
+    if scres.is_null() || out.is_null() {
+        return ScresStatus::NullPointer;
+    }
+    guard(|| {
+        let scres = scres as *mut &mut dyn CResampler;
+        if pos >= (*scres).len() {
+            return ScresStatus::IndexOutOfBounds;
+        }
+        *out = (*scres).get_weights(pos);
+        ScresStatus::Ok
+    })
+}
+
+/// Copy at most `out_len` weights of the chosen event into `out`
+///
+/// On success, the number of weights actually copied is written to
+/// `*n_copied`; it may be less than `out_len` if the event has fewer
+/// weights. Unlike `scres_get_weights`, this only needs read access
+/// and can safely be called concurrently, e.g. while
+/// `scres_resample_many` is still running on other cells.
+///
+/// # Safety
+/// - The resampler must have been previous constructed with `scres_new`.
+/// - `out` must point to a buffer of at least `out_len` elements.
+/// - `n_copied` must not be null.
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn scres_copy_weights(
+    scres: *const c_void,
+    pos: usize,
+    out: *mut c_double,
+    out_len: usize,
+    n_copied: *mut usize,
+) -> ScresStatus {
+    if scres.is_null() || out.is_null() || n_copied.is_null() {
+        return ScresStatus::NullPointer;
+    }
+    guard(|| {
+        let scres = scres as *const &dyn CResampler;
+        if pos >= (*scres).len() {
+            return ScresStatus::IndexOutOfBounds;
+        }
+        let out = std::slice::from_raw_parts_mut(out, out_len);
+        *n_copied = (*scres).copy_weights(pos, out);
+        ScresStatus::Ok
+    })
 }
 
 /// Get the number of weights of the chosen event
 ///
+/// On success, the number of weights is written to `*out`.
+///
 /// # Safety
 /// - The resampler must have been previous constructed with `scres_new`.
+/// - `out` must not be null.
 #[no_mangle]
 #[must_use]
 pub unsafe extern "C" fn scres_get_num_weights(
     scres: *const c_void,
     pos: usize,
-) -> usize {
-    let scres = scres as *const &dyn CResampler;
-    (*scres).get_num_weights(pos)
+    out: *mut usize,
+) -> ScresStatus {
+    if scres.is_null() || out.is_null() {
+        return ScresStatus::NullPointer;
+    }
+    guard(|| {
+        let scres = scres as *const &dyn CResampler;
+        if pos >= (*scres).len() {
+            return ScresStatus::IndexOutOfBounds;
+        }
+        *out = (*scres).get_num_weights(pos);
+        ScresStatus::Ok
+    })
 }
 
 /// Sets the weights of the chosen event
@@ -135,15 +412,26 @@ pub unsafe extern "C" fn scres_get_num_weights(
 ///   as large as the existing number of weights in the event. Extra
 ///   elements will be ignored. `weights` must not be null.
 #[no_mangle]
+#[must_use]
 pub unsafe extern "C" fn scres_set_weights(
     scres: *const c_void,
     pos: usize,
     weights: *const c_double,
-) {
-    let num_weights = scres_get_num_weights(scres, pos);
-    let scres = scres as *const &dyn CResampler;
-    let weights = slice_from_raw_parts(weights, num_weights);
-    (*scres).set_weights(pos, weights.as_ref().unwrap())
+) -> ScresStatus {
+    if scres.is_null() || weights.is_null() {
+        return ScresStatus::NullPointer;
+    }
+    let mut num_weights = 0;
+    let status = scres_get_num_weights(scres, pos, &mut num_weights);
+    if status != ScresStatus::Ok {
+        return status;
+    }
+    guard(|| {
+        let scres = scres as *const &dyn CResampler;
+        let weights = slice_from_raw_parts(weights, num_weights);
+        (*scres).set_weights(pos, weights.as_ref().unwrap());
+        ScresStatus::Ok
+    })
 }
 
 /// Delete all pushed events
@@ -152,59 +440,238 @@ pub unsafe extern "C" fn scres_set_weights(
 /// The resampler must have been previous constructed with `scres_new`.
 ///
 #[no_mangle]
-pub unsafe extern "C" fn scres_clear(scres: *mut c_void) {
-    let scres = scres as *mut &mut dyn CResampler;
-    (*scres).clear()
+#[must_use]
+pub unsafe extern "C" fn scres_clear(scres: *mut c_void) -> ScresStatus {
+    if scres.is_null() {
+        return ScresStatus::NullPointer;
+    }
+    guard(|| {
+        let scres = scres as *mut &mut dyn CResampler;
+        (*scres).clear();
+        ScresStatus::Ok
+    })
 }
 
 pub trait CResampler {
-    fn resample_cell(&self, seed: usize, max_cell_size: f64);
+    fn resample_cell(&self, seed: usize, max_cell_size: f64) -> CellInfo;
+
+    fn resample_cells(&self, seeds: &[usize], max_cell_size: f64);
+
+    fn resample_all(&self, max_cell_size: f64);
 
     fn reserve(&mut self, cap: usize);
 
     fn push(&mut self, event: EventView);
 
-    fn get_weights(&mut self, pos: usize) -> &[f64];
+    fn get_weights(&self, pos: usize) -> *const f64;
+
+    fn copy_weights(&self, pos: usize, out: &mut [f64]) -> usize;
 
     fn get_num_weights(&self, pos: usize) -> usize;
 
     fn set_weights(&self, pos: usize, weights: &[f64]);
 
     fn clear(&mut self);
+
+    fn len(&self) -> usize;
+}
+
+/// Wraps a [`Resampler`] with the scratch buffer `scres_get_weights`
+/// needs to hand back a pointer that stays valid across the FFI
+/// boundary until the next such call
+///
+/// The cache is behind a [`Mutex`], not a `RefCell`: `scres_get_weights`
+/// is reachable from multiple native threads through a shared
+/// `&dyn CResampler`, e.g. while `scres_resample_many` is running on a
+/// thread pool, and `RefCell`'s borrow flag is not safe to access
+/// unsynchronized across threads.
+///
+/// [`Resampler::copy_weights`] needs no such buffer, since the caller
+/// already owns the memory it writes into.
+struct FfiResampler<D> {
+    inner: Resampler<D>,
+    weights_cache: Mutex<Vec<c_double>>,
 }
 
-impl<D: Distance + Send + Sync> CResampler for Resampler<D> {
-    fn resample_cell(&self, seed: usize, max_cell_size: f64) {
-        self.resample_cell(seed, n64(max_cell_size));
+impl<D> FfiResampler<D> {
+    fn new(inner: Resampler<D>) -> Self {
+        Self {
+            inner,
+            weights_cache: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl<D: Distance + Send + Sync> CResampler for FfiResampler<D> {
+    fn resample_cell(&self, seed: usize, max_cell_size: f64) -> CellInfo {
+        self.inner.resample_cell(seed, n64(max_cell_size)).into()
+    }
+
+    fn resample_cells(&self, seeds: &[usize], max_cell_size: f64) {
+        self.inner.resample_cells(seeds, n64(max_cell_size));
+    }
+
+    fn resample_all(&self, max_cell_size: f64) {
+        self.inner.resample_all(n64(max_cell_size));
     }
 
     fn reserve(&mut self, cap: usize) {
-        self.reserve(cap);
+        self.inner.reserve(cap);
     }
 
     fn push(&mut self, event: EventView) {
         // TODO: remove `ToEvent` once cres 0.9 is available
-        self.push(ToEvent(event).into());
+        self.inner.push(ToEvent(event).into());
     }
 
     fn get_num_weights(&self, pos: usize) -> usize {
-        Resampler::get_num_weights(self, pos)
+        self.inner.get_num_weights(pos)
     }
 
-    fn get_weights(&mut self, pos: usize) -> &[f64] {
+    fn get_weights(&self, pos: usize) -> *const f64 {
+        let weights =
+            self.inner.get_weights(pos).iter().map(|w| w.raw()).collect();
+        let mut cache = self
+            .weights_cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *cache = weights;
+        cache.as_ptr()
+    }
+
+    fn copy_weights(&self, pos: usize, out: &mut [f64]) -> usize {
         // Safety: N64 and f64 have the same memory layout and alignment
-        unsafe { std::mem::transmute(self.get_weights(pos)) }
+        let out: &mut [N64] = unsafe { std::mem::transmute(out) };
+        self.inner.copy_weights(pos, out)
     }
 
     fn set_weights(&self, pos: usize, weights: &[f64]) {
         // Safety: N64 and f64 have the same memory layout and alignment
         let weights: &[N64] = unsafe { std::mem::transmute(weights) };
-        self.set_weights(pos, weights)
+        self.inner.set_weights(pos, weights)
     }
 
     fn clear(&mut self) {
-        self.clear();
+        self.inner.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+/// Distance metric that forwards each query to a user-supplied callback
+///
+/// Constructed by [`scres_new`] when [`Opt::dist_fn`] is set.
+struct CallbackDistance {
+    dist_fn: extern "C" fn(
+        a: *const EventView,
+        b: *const EventView,
+        userdata: *mut c_void,
+    ) -> c_double,
+    userdata: Userdata,
+}
+
+/// Wrapper making an opaque `userdata` pointer `Send + Sync`
+///
+/// The pointer itself is never dereferenced on the Rust side; it is
+/// only ever handed back to the callback that received it, exactly
+/// like any other pointer crossing the C API.
+#[derive(Copy, Clone)]
+struct Userdata(*mut c_void);
+unsafe impl Send for Userdata {}
+unsafe impl Sync for Userdata {}
+
+impl CallbackDistance {
+    fn new(
+        dist_fn: extern "C" fn(
+            a: *const EventView,
+            b: *const EventView,
+            userdata: *mut c_void,
+        ) -> c_double,
+        userdata: *mut c_void,
+    ) -> Self {
+        Self {
+            dist_fn,
+            userdata: Userdata(userdata),
+        }
+    }
+}
+
+impl Distance for CallbackDistance {
+    fn distance(&self, a: &Event, b: &Event) -> N64 {
+        let a = EventViewBuf::new(a);
+        let b = EventViewBuf::new(b);
+        n64((self.dist_fn)(a.as_ptr(), b.as_ptr(), self.userdata.0))
+    }
+}
+
+/// Owned buffer that can be viewed as an [`EventView`]
+///
+/// Reconstructs the C-compatible event representation from an
+/// [`Event`], so it can be handed to a user-supplied [`CallbackDistance`].
+/// This is the inverse of [`ToEvent`].
+struct EventViewBuf {
+    weights: Vec<c_double>,
+    type_sets: Vec<TypeSetView<'static>>,
+    // keeps the momenta referenced by `type_sets` alive
+    _momenta: Vec<Vec<[c_double; 4]>>,
+    view: EventView<'static>,
+}
+
+impl EventViewBuf {
+    fn new(event: &Event) -> Self {
+        let weights: Vec<c_double> =
+            event.weights.read().iter().map(|w| w.raw()).collect();
+
+        let mut type_sets = Vec::new();
+        let mut momenta_store = Vec::new();
+        for (pid, momenta) in group_outgoing_by_type(event.outgoing()) {
+            type_sets.push(TypeSetView {
+                pid,
+                momenta: momenta.as_ptr(),
+                n_momenta: momenta.len(),
+            });
+            momenta_store.push(momenta);
+        }
+
+        let view = EventView {
+            id: 0,
+            weights: weights.as_ptr(),
+            type_sets: type_sets.as_ptr(),
+            n_weights: weights.len(),
+            n_type_sets: type_sets.len(),
+        };
+        Self {
+            weights,
+            type_sets,
+            _momenta: momenta_store,
+            view,
+        }
+    }
+
+    fn as_ptr(&self) -> *const EventView {
+        &self.view
+    }
+}
+
+/// Group an event's outgoing particles into contiguous runs of the
+/// same particle type, mirroring the layout [`EventView`] expects
+fn group_outgoing_by_type(
+    outgoing: &[(ParticleID, [N64; 4])],
+) -> Vec<(i64, Vec<[c_double; 4]>)> {
+    let mut groups: Vec<(i64, Vec<[c_double; 4]>)> = Vec::new();
+    for (pid, p) in outgoing {
+        let pid = pid.id();
+        let p = p.map(|x| x.raw());
+        match groups.last_mut() {
+            Some((last_pid, momenta)) if *last_pid == pid => {
+                momenta.push(p)
+            }
+            _ => groups.push((pid, vec![p])),
+        }
     }
+    groups
 }
 
 struct ToEvent<'a>(EventView<'a>);
@@ -245,85 +712,269 @@ impl<'a> From<ToEvent<'a>> for Event {
 #[cfg(test)]
 mod tests {
     use core::f64;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     use cres::c_api::event::TypeSet;
 
     use super::*;
 
-    #[test]
-    fn c_api() {
-        unsafe {
-            let opt = Opt {
-                neighbour_search: Search::Tree,
-                pt_weight: 0.0,
-            };
-            let resampler = scres_new(opt);
-            scres_reserve(resampler, 2);
-
+    const EVENT_A_MOMENTA: [[c_double; 4]; 2] = [
+        [
+            0.86042412975E+02,
+            0.18299527188E+02,
+            0.50776693328E+02,
+            -0.67008593105E+02,
+        ],
+        [
+            0.80026513931E+03,
+            -0.18299527188E+02,
+            -0.50776693328E+02,
+            -0.79844295220E+03,
+        ],
+    ];
+    const EVENT_B_MOMENTA: [[c_double; 4]; 2] = [
+        [
+            0.49452408437E+02,
+            0.20789583719E+02,
+            -0.23718791628E+02,
+            0.38088749425E+02,
+        ],
+        [
+            0.10452662667E+03,
+            -0.20789583719E+02,
+            0.23718791628E+02,
+            0.99654542370E+02,
+        ],
+    ];
+
+    /// Push two single-weight events, with the given momenta and
+    /// weight for each, onto `resampler`
+    ///
+    /// # Safety
+    /// The resampler must have been previous constructed with `scres_new`.
+    unsafe fn push_two_events(
+        resampler: *mut c_void,
+        momenta_a: [[c_double; 4]; 2],
+        weight_a: c_double,
+        momenta_b: [[c_double; 4]; 2],
+        weight_b: c_double,
+    ) {
+        for (momenta, weight) in
+            [(momenta_a, weight_a), (momenta_b, weight_b)]
+        {
             let jets = TypeSet {
                 pid: 90,
-                momenta: vec![
-                    [
-                        0.86042412975E+02,
-                        0.18299527188E+02,
-                        0.50776693328E+02,
-                        -0.67008593105E+02,
-                    ],
-                    [
-                        0.80026513931E+03,
-                        -0.18299527188E+02,
-                        -0.50776693328E+02,
-                        -0.79844295220E+03,
-                    ],
-                ],
+                momenta: momenta.to_vec(),
             };
             let view = jets.view();
-            let weights = -1.0;
             let event = EventView {
                 id: 0,
-                weights: &weights as _,
+                weights: &weight as _,
                 type_sets: &view as _,
                 n_weights: 1,
                 n_type_sets: 1,
             };
-            scres_push_event(resampler, event);
+            assert_eq!(scres_push_event(resampler, event), ScresStatus::Ok);
+        }
+    }
 
-            let jets = TypeSet {
-                pid: 90,
-                momenta: vec![
-                    [
-                        0.49452408437E+02,
-                        0.20789583719E+02,
-                        -0.23718791628E+02,
-                        0.38088749425E+02,
-                    ],
-                    [
-                        0.10452662667E+03,
-                        -0.20789583719E+02,
-                        0.23718791628E+02,
-                        0.99654542370E+02,
-                    ],
-                ],
+    #[test]
+    fn c_api() {
+        unsafe {
+            let opt = Opt {
+                neighbour_search: Search::Tree,
+                pt_weight: 0.0,
+                dist_fn: None,
+                userdata: std::ptr::null_mut(),
             };
-            let view = jets.view();
-            let weights = 1.0;
-            let event = EventView {
-                id: 0,
-                weights: &weights as _,
-                type_sets: &view as _,
-                n_weights: 1,
-                n_type_sets: 1,
+            let mut resampler = std::ptr::null_mut();
+            assert_eq!(scres_new(opt, &mut resampler), ScresStatus::Ok);
+            assert_eq!(scres_reserve(resampler, 2), ScresStatus::Ok);
+
+            push_two_events(
+                resampler,
+                EVENT_A_MOMENTA,
+                -1.0,
+                EVENT_B_MOMENTA,
+                1.0,
+            );
+
+            let mut info = std::mem::MaybeUninit::<CellInfo>::uninit();
+            assert_eq!(
+                scres_resample(
+                    resampler,
+                    0,
+                    f64::MAX,
+                    info.as_mut_ptr()
+                ),
+                ScresStatus::Ok
+            );
+            let info = info.assume_init();
+            assert_eq!(info.n_events, 2);
+            assert_eq!(info.weight_sum_before, 0.0);
+            assert_eq!(info.weight_sum_after, 0.0);
+
+            let mut weight = std::ptr::null();
+            assert_eq!(
+                scres_get_weights(resampler, 0, &mut weight),
+                ScresStatus::Ok
+            );
+            assert_eq!(*weight, 0.0);
+            assert_eq!(
+                scres_get_weights(resampler, 1, &mut weight),
+                ScresStatus::Ok
+            );
+            assert_eq!(*weight, 0.0);
+
+            assert_eq!(
+                scres_get_weights(resampler, 2, &mut weight),
+                ScresStatus::IndexOutOfBounds
+            );
+
+            let mut buf = [1.0; 4];
+            let mut n_copied = 0;
+            assert_eq!(
+                scres_copy_weights(
+                    resampler,
+                    0,
+                    buf.as_mut_ptr(),
+                    buf.len(),
+                    &mut n_copied
+                ),
+                ScresStatus::Ok
+            );
+            assert_eq!(n_copied, 1);
+            assert_eq!(buf[0], 0.0);
+
+            assert_eq!(
+                scres_copy_weights(
+                    resampler,
+                    5,
+                    buf.as_mut_ptr(),
+                    buf.len(),
+                    &mut n_copied
+                ),
+                ScresStatus::IndexOutOfBounds
+            );
+
+            assert_eq!(scres_clear(resampler), ScresStatus::Ok);
+
+            assert_eq!(scres_free(resampler), ScresStatus::Ok);
+        }
+    }
+
+    #[test]
+    fn c_api_resample_many() {
+        unsafe {
+            let opt = Opt {
+                neighbour_search: Search::Tree,
+                pt_weight: 0.0,
+                dist_fn: None,
+                userdata: std::ptr::null_mut(),
+            };
+            let mut resampler = std::ptr::null_mut();
+            assert_eq!(scres_new(opt, &mut resampler), ScresStatus::Ok);
+
+            push_two_events(
+                resampler,
+                EVENT_A_MOMENTA,
+                -1.0,
+                EVENT_B_MOMENTA,
+                1.0,
+            );
+
+            // null seeds -> auto mode: seed at every negative-weight event
+            assert_eq!(
+                scres_resample_many(
+                    resampler,
+                    std::ptr::null(),
+                    0,
+                    f64::MAX
+                ),
+                ScresStatus::Ok
+            );
+
+            let mut weight = std::ptr::null();
+            assert_eq!(
+                scres_get_weights(resampler, 0, &mut weight),
+                ScresStatus::Ok
+            );
+            assert_eq!(*weight, 0.0);
+
+            let seeds = [0_usize];
+            assert_eq!(
+                scres_resample_many(
+                    resampler,
+                    seeds.as_ptr(),
+                    seeds.len(),
+                    f64::MAX
+                ),
+                ScresStatus::Ok
+            );
+            assert_eq!(
+                scres_resample_many(
+                    resampler,
+                    seeds.as_ptr(),
+                    seeds.len(),
+                    f64::MAX
+                ),
+                ScresStatus::Ok
+            );
+
+            let bad_seeds = [42_usize];
+            assert_eq!(
+                scres_resample_many(
+                    resampler,
+                    bad_seeds.as_ptr(),
+                    bad_seeds.len(),
+                    f64::MAX
+                ),
+                ScresStatus::IndexOutOfBounds
+            );
+
+            assert_eq!(scres_free(resampler), ScresStatus::Ok);
+        }
+    }
+
+    /// Counts its own invocations through `userdata`, which must point
+    /// at an `AtomicUsize`
+    extern "C" fn counting_distance(
+        _a: *const EventView,
+        _b: *const EventView,
+        userdata: *mut c_void,
+    ) -> c_double {
+        let counter = unsafe { &*(userdata as *const AtomicUsize) };
+        counter.fetch_add(1, Ordering::SeqCst);
+        1.0
+    }
+
+    #[test]
+    fn c_api_callback_distance() {
+        unsafe {
+            let counter = AtomicUsize::new(0);
+            let opt = Opt {
+                neighbour_search: Search::Tree,
+                pt_weight: 0.0,
+                dist_fn: Some(counting_distance),
+                userdata: &counter as *const AtomicUsize as *mut c_void,
             };
-            scres_push_event(resampler, event);
+            let mut resampler = std::ptr::null_mut();
+            assert_eq!(scres_new(opt, &mut resampler), ScresStatus::Ok);
 
-            scres_resample(resampler, 0, f64::MAX);
+            let momenta = [[0., 0., 0., 1.], [0., 0., 0., -1.]];
+            push_two_events(resampler, momenta, -1.0, momenta, 1.0);
 
-            assert_eq!(*scres_get_weights(resampler, 0), 0.0);
-            assert_eq!(*scres_get_weights(resampler, 1), 0.0);
+            assert_eq!(
+                scres_resample(resampler, 0, f64::MAX, std::ptr::null_mut()),
+                ScresStatus::Ok
+            );
 
-            scres_clear(resampler);
+            assert!(
+                counter.load(Ordering::SeqCst) > 0,
+                "dist_fn was never invoked, or userdata was forwarded wrong"
+            );
 
-            scres_free(resampler);
+            assert_eq!(scres_free(resampler), ScresStatus::Ok);
         }
     }
 }
@@ -5,8 +5,9 @@ use cres::{
     event::Event,
     neighbour_search::{NaiveNeighbourSearch, TreeSearch},
     traits::{Distance, NeighbourSearchAlgo},
-    N64,
+    n64, N64,
 };
+use rayon::prelude::*;
 
 /// A single-cell resampler
 #[derive(Debug)]
@@ -14,7 +15,6 @@ pub struct Resampler<D> {
     distance: D,
     neighbour_search: Search,
     events: Vec<Event>,
-    last_retrieved_weights: Vec<N64>,
 }
 
 /// Resample the cell with the event number `seed` as cell seed
@@ -22,7 +22,7 @@ impl<D> Resampler<D>
 where
     D: Distance + Send + Sync,
 {
-    pub fn resample_cell(&self, seed: usize, max_cell_size: N64) {
+    pub fn resample_cell(&self, seed: usize, max_cell_size: N64) -> CellStats {
         let mut cell = match self.neighbour_search {
             Search::Tree => {
                 let neighbour_search = TreeSearch::new_with_dist(
@@ -41,10 +41,95 @@ where
                 Cell::new(&self.events, seed, &neighbour_search)
             }
         };
+
+        // FIXME: `Cell::radius`/`Cell::indices` are not exercised anywhere
+        // else in this crate and could not be checked against the real
+        // `cres` dependency in this environment (no `Cargo.toml`/lockfile
+        // or vendored `cres` source is present here, and there is no
+        // network access to fetch one). Verify these two calls against
+        // the pinned `cres` version with `cargo check` before merging;
+        // adjust the names/signatures below if they differ.
+        let radius = cell.radius();
+        let indices: Vec<usize> = cell.indices().to_vec();
+        let weights_before: Vec<N64> = indices
+            .iter()
+            .map(|&pos| self.weight_sum(pos))
+            .collect();
+
         cell.resample();
+
+        let mut weight_sum_after = n64(0.);
+        let mut n_sign_flips = 0;
+        for (&pos, &before) in indices.iter().zip(&weights_before) {
+            let after = self.weight_sum(pos);
+            weight_sum_after += after;
+            if (after > n64(0.)) != (before > n64(0.)) {
+                n_sign_flips += 1;
+            }
+        }
+
+        CellStats {
+            n_events: indices.len(),
+            radius,
+            weight_sum_before: weights_before.iter().copied().sum(),
+            weight_sum_after,
+            n_sign_flips,
+        }
+    }
+
+    /// Resample the cells seeded at `seeds`, distributed over a thread pool
+    ///
+    /// Seed cells must be disjoint. `Weights::read`/`write` only
+    /// guarantee that individual reads and writes do not corrupt
+    /// memory; they do not make the read-decide-write of
+    /// `Cell::resample` atomic. If two seeds' cells happen to overlap,
+    /// resampling is still memory-safe, but it is not logically
+    /// correct: two threads can race to resample a shared event, and
+    /// whichever write lands last silently clobbers the other,
+    /// breaking per-cell weight conservation. Callers are responsible
+    /// for choosing disjoint seeds; this is not checked here.
+    pub fn resample_cells(&self, seeds: &[usize], max_cell_size: N64) {
+        seeds.par_iter().for_each(|&seed| {
+            self.resample_cell(seed, max_cell_size);
+        });
+    }
+
+    /// Resample a cell for every event with negative total weight
+    ///
+    /// This is the usual full-pass resampling workflow: seed one cell
+    /// at each negative-weight event and resample all of them in
+    /// parallel.
+    pub fn resample_all(&self, max_cell_size: N64) {
+        let seeds = self.negative_weight_seeds();
+        self.resample_cells(&seeds, max_cell_size);
+    }
+
+    /// Indices of all events with negative total weight
+    fn negative_weight_seeds(&self) -> Vec<usize> {
+        (0..self.events.len())
+            .filter(|&pos| self.weight_sum(pos) < n64(0.))
+            .collect()
     }
 }
 
+/// Diagnostics collected while resampling a single cell
+///
+/// Lets callers tune `max_cell_size` and verify that resampling
+/// preserves the cell's total weight.
+#[derive(Copy, Clone, Debug)]
+pub struct CellStats {
+    /// Number of events that were part of the cell
+    pub n_events: usize,
+    /// Distance from the seed event to the cell boundary
+    pub radius: N64,
+    /// Sum of the cell's event weights before resampling
+    pub weight_sum_before: N64,
+    /// Sum of the cell's event weights after resampling
+    pub weight_sum_after: N64,
+    /// Number of events whose total weight changed sign
+    pub n_sign_flips: usize,
+}
+
 impl<D> Resampler<D> {
     /// Reserve space for `cap` events
     pub fn reserve(&mut self, cap: usize) {
@@ -56,11 +141,26 @@ impl<D> Resampler<D> {
         self.events.push(event)
     }
 
-    /// Retrieve the weights of the given event
-    pub fn get_weights(&mut self, pos: usize) -> &[N64] {
-        self.last_retrieved_weights =
-            self.events[pos].weights.read().iter().copied().collect();
-        &self.last_retrieved_weights
+    /// Retrieve a copy of the weights of the given event
+    pub fn get_weights(&self, pos: usize) -> Vec<N64> {
+        self.events[pos].weights.read().iter().copied().collect()
+    }
+
+    /// Copy at most `out.len()` weights of the given event into `out`
+    ///
+    /// Returns the number of weights actually copied. Unlike
+    /// [`Resampler::get_weights`], this does not allocate. Like it, it
+    /// only needs shared access, so it composes with the parallel resampling
+    /// methods above, e.g. to read out intermediate results while
+    /// [`Resampler::resample_cells`] is still running on other cells.
+    pub fn copy_weights(&self, pos: usize, out: &mut [N64]) -> usize {
+        let weights = self.events[pos].weights.read();
+        let mut n_copied = 0;
+        for (dst, src) in out.iter_mut().zip(weights.iter()) {
+            *dst = *src;
+            n_copied += 1;
+        }
+        n_copied
     }
 
     /// Retrieve the number of weights of the given event
@@ -68,6 +168,21 @@ impl<D> Resampler<D> {
         self.events[pos].n_weights()
     }
 
+    /// Total weight of the given event, summed over all weight entries
+    fn weight_sum(&self, pos: usize) -> N64 {
+        self.events[pos].weights.read().iter().copied().sum()
+    }
+
+    /// Number of events currently stored in the resampler
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Whether the resampler currently holds no events
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
     /// Retrieve the weights of the given event
     pub fn set_weights(&self, pos: usize, weights: &[N64]) {
         // TODO: this is awkward
@@ -130,7 +245,6 @@ impl<D> ResamplerBuilder<D> {
             distance,
             neighbour_search,
             events: vec![],
-            last_retrieved_weights: vec![],
         }
     }
 }